@@ -1,7 +1,7 @@
-use crate::symbols::CRATE;
+use crate::symbols::{CRATE, KEY};
 use syn::{
     parse::{Parse, ParseStream},
-    parse_quote, Error, Ident, Path, Token,
+    parse_quote, Error, Ident, LitStr, Path, Token,
 };
 
 pub struct Inject {
@@ -32,3 +32,32 @@ impl Parse for Inject {
         }
     }
 }
+
+/// The contents of a per-argument `#[inject]` attribute, e.g.
+/// `#[inject(key = "postgres_pool")]`.
+pub struct InjectArg {
+    pub key: Option<LitStr>,
+}
+
+impl Parse for InjectArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Ok(Self { key: None });
+        }
+        let ident: Ident = input.parse()?;
+        if ident != KEY {
+            return Err(Error::new(input.span(), "expected `key` or no params"));
+        }
+
+        let _eq: Token![=] = input.parse()?;
+        let key = input.parse()?;
+        if input.is_empty() {
+            Ok(Self { key: Some(key) })
+        } else {
+            Err(Error::new(
+                input.span(),
+                "unexpected tokens at the end of inject field attribute",
+            ))
+        }
+    }
+}
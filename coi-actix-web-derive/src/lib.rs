@@ -3,7 +3,7 @@
 //! [`coi-actix-web`]: https://docs.rs/coi-actix-web
 
 extern crate proc_macro;
-use crate::attr::Inject;
+use crate::attr::{Inject, InjectArg as InjectArgAttr};
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::{
@@ -14,12 +14,56 @@ use syn::{
 mod attr;
 mod symbols;
 
-fn get_arc_ty(ty: &Type, type_path: &TypePath) -> Result<Type> {
-    let make_arc_error = || Err(Error::new_spanned(ty, "only Arc<...> can be injected"));
+/// The type behind a single `#[inject]` argument: the trait object type
+/// passed to [`ContainerKey`] (e.g. `dyn IService`), and whether the
+/// argument was wrapped in `Option<...>` and should therefore resolve to
+/// `None` instead of failing the request when the container doesn't have
+/// a provider for it.
+///
+/// [`ContainerKey`]: ../coi_actix_web/trait.ContainerKey.html
+struct InjectArg {
+    inner: Type,
+    optional: bool,
+}
+
+fn get_arc_ty(ty: &Type, type_path: &TypePath) -> Result<InjectArg> {
+    let make_arc_error = || {
+        Err(Error::new_spanned(
+            ty,
+            "only Arc<...> or Option<Arc<...>> can be injected",
+        ))
+    };
     if type_path.path.leading_colon.is_some() || type_path.path.segments.len() != 1 {
         return make_arc_error();
     }
     let segment = &type_path.path.segments[0];
+    if segment.ident == "Option" {
+        let angle_args = match &segment.arguments {
+            PathArguments::AngleBracketed(angle_args) => angle_args,
+            _ => return make_arc_error(),
+        };
+        let args = &angle_args.args;
+        if args.len() != 1 {
+            return make_arc_error();
+        }
+        let inner_ty = match &args[0] {
+            GenericArgument::Type(ty) => ty,
+            _ => return make_arc_error(),
+        };
+        let inner_type_path = match inner_ty {
+            Type::Path(type_path) => type_path,
+            _ => return make_arc_error(),
+        };
+        let inner = get_arc_ty(inner_ty, inner_type_path)?;
+        if inner.optional {
+            return make_arc_error();
+        }
+        return Ok(InjectArg {
+            inner: inner.inner,
+            optional: true,
+        });
+    }
+
     if segment.ident != "Arc" {
         return make_arc_error();
     }
@@ -33,7 +77,10 @@ fn get_arc_ty(ty: &Type, type_path: &TypePath) -> Result<Type> {
     }
 
     if let GenericArgument::Type(ty) = &args[0] {
-        Ok(ty.clone())
+        Ok(InjectArg {
+            inner: ty.clone(),
+            optional: false,
+        })
     } else {
         make_arc_error()
     }
@@ -59,14 +106,48 @@ fn get_arc_ty(ty: &Type, type_path: &TypePath) -> Result<Type> {
 /// }
 /// ```
 ///
+/// An injected argument may also be wrapped in `Option<...>`, e.g.
+/// `#[inject] service: Option<Arc<dyn IService>>`, in which case it resolves
+/// to `None` rather than failing the request when the container has no
+/// provider registered for it.
+///
+/// By default the container key used to resolve an argument is its
+/// identifier, e.g. `service` above resolves the key `"service"`. Use
+/// `#[inject(key = "...")]` to resolve a different key than the argument's
+/// name, e.g. `#[inject(key = "postgres_pool")] pool: Arc<dyn IPool>`. A
+/// bare `#[inject]` and a keyed `#[inject(key = "...")]` can be mixed
+/// freely on the same handler:
+///
+/// ```rust,no_run
+/// use actix_web::Responder;
+/// use coi::Inject;
+/// use coi_actix_web::inject;
+///
+/// # trait IService : Inject {}
+/// # trait IPool : Inject {}
+///
+/// #[inject]
+/// async fn get_all(
+///     #[inject] service: Arc<dyn IService>,
+///     #[inject(key = "postgres_pool")] pool: Arc<dyn IPool>,
+/// ) -> Result<impl Responder, ()> {
+///     //...
+///     Ok("Hello, World")
+/// }
+/// ```
+///
 /// This proc macro changes the input arguments to the fn that it's applied to. All `#[inject]` args
-/// get collected into a single type and are pattern matched out. This is to take advantage of the
-/// [`coi-actix-web`] crate's `FromResponse` impls. By ensuring that all injected types are part of
-/// the same type, we can guarantee that all injected types are resolved from the same scoped
-/// container. The downside of this is that the signature you see is not what is generated, and
-/// this makes manually calling these functions more verbose. Since all of these functions are
-/// expected to be passed to [`actix-web`]'s routing APIs, it's not an issue since those are all
-/// generic.
+/// get collected into a single recursive `Injected` type and are pattern matched out. This is to
+/// take advantage of the [`coi-actix-web`] crate's `FromRequest` impls. By ensuring that all
+/// injected types are part of the same type, we can guarantee that all injected types are
+/// resolved from the same scoped container (see [`coi_actix_web::ScopedContainer`] for how that
+/// scope is also shared across a whole request). The downside of this is that the signature you
+/// see is not what is generated, and this makes manually calling these functions more verbose.
+/// Since all of these functions are expected to be passed to [`actix-web`]'s routing APIs, it's
+/// not an issue since those are all generic. There's no limit on the number of `#[inject]`
+/// arguments a handler can take.
+///
+/// [`coi_actix_web::ScopedContainer`]: https://docs.rs/coi-actix-web/latest/coi_actix_web/struct.ScopedContainer.html
 ///
 /// [`coi-actix-web`]: https://docs.rs/coi-actix-web
 /// [`actix-web`]: https://docs.rs/actix-web
@@ -101,7 +182,7 @@ pub fn inject(attr: TokenStream, input: TokenStream) -> TokenStream {
     }
 
     let num_args = inject.len();
-    let (key, ty): (Vec<Result<Ident>>, Vec<Result<Type>>) = inject
+    let (key, rest): (Vec<Result<Ident>>, Vec<(Result<InjectArg>, Result<Option<syn::LitStr>>)>) = inject
         .into_iter()
         .map(|arg| match arg.value() {
             FnArg::Typed(arg) => {
@@ -118,33 +199,59 @@ pub fn inject(attr: TokenStream, input: TokenStream) -> TokenStream {
                 } else {
                     Err(Error::new_spanned(
                         &*arg.ty,
-                        "only Arc<...> can be injected",
+                        "only Arc<...> or Option<Arc<...>> can be injected",
                     ))
                 };
-                (pat, ty)
+
+                let key_override = arg
+                    .attrs
+                    .iter()
+                    .find(|attr| attr.path.is_ident("inject"))
+                    .map(|attr| {
+                        if attr.tokens.is_empty() {
+                            Ok(None)
+                        } else {
+                            attr.parse_args::<InjectArgAttr>().map(|a| a.key)
+                        }
+                    })
+                    .transpose()
+                    .map(Option::flatten);
+
+                (pat, (ty, key_override))
             }
             _ => unreachable!(),
         })
         .unzip();
+    let (arg, key_override): (Vec<Result<InjectArg>>, Vec<Result<Option<syn::LitStr>>>) =
+        rest.into_iter().unzip();
     let key = match key.into_iter().collect::<Result<Vec<_>>>() {
         Ok(key) => key,
         Err(e) => return e.to_compile_error().into(),
     };
-    let ty = match ty.into_iter().collect::<Result<Vec<_>>>() {
-        Ok(ty) => ty,
+    let arg = match arg.into_iter().collect::<Result<Vec<_>>>() {
+        Ok(arg) => arg,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let key_override = match key_override.into_iter().collect::<Result<Vec<_>>>() {
+        Ok(key_override) => key_override,
         Err(e) => return e.to_compile_error().into(),
     };
     let (defs, container_key): (Vec<_>, Vec<_>) = key
         .iter()
-        .zip(ty.iter())
-        .map(|(key, ty)| {
+        .zip(arg.iter())
+        .zip(key_override.iter())
+        .map(|((key, arg), key_override)| {
             let ident = format_ident!("__{}_{}_Key", fn_ident, key);
-            let key_str = format!("{}", key);
+            let key_str = match key_override {
+                Some(key_override) => key_override.value(),
+                None => format!("{}", key),
+            };
+            let inner = &arg.inner;
             (
                 quote! {
                     #[allow(non_camel_case_types)]
                     struct #ident;
-                    impl #caw::ContainerKey<#ty> for #ident {
+                    impl #caw::ContainerKey<#inner> for #ident {
                         const KEY: &'static str = #key_str;
                     }
                 },
@@ -152,22 +259,46 @@ pub fn inject(attr: TokenStream, input: TokenStream) -> TokenStream {
             )
         })
         .unzip();
+    let wrapped_ty: Vec<Type> = arg
+        .iter()
+        .map(|arg| {
+            let inner = &arg.inner;
+            if arg.optional {
+                parse_quote! { ::std::option::Option<::std::sync::Arc<#inner>> }
+            } else {
+                parse_quote! { ::std::sync::Arc<#inner> }
+            }
+        })
+        .collect();
 
-    let injected_arg = if num_args > 1 {
-        let injected_n = format_ident!("Injected{}", num_args);
-        parse_quote! {
-            #caw::#injected_n (( #(
-                #caw::Injected(#key),
-            )* _ )) :
-            #caw::#injected_n<#( #ty, )* #( #container_key, )*>
-        }
-    } else {
-        parse_quote! {
-            #caw::Injected(#( #key, )* _):
-            #caw::Injected<#( ::std::sync::Arc<#ty>, )* #( #container_key, )*>
+    if num_args == 1 {
+        let key = &key[0];
+        let wrapped_ty = &wrapped_ty[0];
+        let container_key = &container_key[0];
+        inputs.push(parse_quote! {
+            #caw::Injected(#key, _): #caw::Injected<#wrapped_ty, #container_key>
+        });
+    } else if num_args > 1 {
+        // Fold the collected args, right to left, into the recursive
+        // head/tail structure that `Injected`'s `FromRequest` impl expects,
+        // bottoming out at `()` for both the value and key lists.
+        let mut value_pat: Pat = parse_quote! { _ };
+        let mut ty: Type = parse_quote! { () };
+        let mut key_ty: Type = parse_quote! { () };
+        for ((key, wrapped_ty), container_key) in key
+            .iter()
+            .zip(wrapped_ty.iter())
+            .zip(container_key.iter())
+            .rev()
+        {
+            value_pat = parse_quote! { (#key, #value_pat) };
+            ty = parse_quote! { (#wrapped_ty, #ty) };
+            key_ty = parse_quote! { (#container_key, #key_ty) };
         }
-    };
-    inputs.push(injected_arg);
+        inputs.push(parse_quote! {
+            #caw::Injected(#value_pat, _): #caw::Injected<#ty, #key_ty>
+        });
+    }
 
     let expanded = quote! {
         #( #defs )*
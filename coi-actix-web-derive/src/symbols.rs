@@ -5,6 +5,7 @@ use syn::{Ident, Path};
 pub struct Symbol(&'static str);
 
 pub const CRATE: Symbol = Symbol("crate");
+pub const KEY: Symbol = Symbol("key");
 
 impl PartialEq<Symbol> for Ident {
     fn eq(&self, sym: &Symbol) -> bool {
@@ -160,9 +160,10 @@
 //! }
 //! ```
 
-use actix_service::ServiceFactory;
-use actix_web::dev::ServiceRequest;
-use actix_web::Error;
+use actix_service::{forward_ready, Service, ServiceFactory, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::{Error, HttpMessage};
+use futures::future::{LocalBoxFuture, Ready as TransformReady};
 
 /// Extensions to `actix-web`'s `App` struct
 pub trait AppExt {
@@ -229,12 +230,74 @@ pub trait AppExt {
     /// #     let _ = service;
     /// #     Ok(HttpResponse::Ok())
     /// # }
-    ///  
+    ///
     /// ```
     fn register_container(self, container: Container) -> Self;
+
+    /// Registers a [`ContainerErrorConfig`] so that failed `#[inject]`
+    /// resolutions are mapped to an `actix_web::Error` through `handler`
+    /// instead of the default `ErrorInternalServerError`.
+    ///
+    /// This only covers resolution failures, i.e. a [`coi::Error`] returned
+    /// while resolving an `#[inject]` argument out of an already-located
+    /// `Container`. It is not consulted when no `Container` could be found
+    /// for the request at all (see [`scoped_container`]); that's a setup
+    /// error rather than a DI resolution error, and still surfaces as the
+    /// default `ErrorInternalServerError`.
+    fn container_error_handler<F>(self, handler: F) -> Self
+    where
+        F: Fn(&HttpRequest, coi::Error) -> Error + Send + Sync + 'static;
 }
 
 impl<T> AppExt for actix_web::App<T>
+where
+    T: ServiceFactory<ServiceRequest, Config = (), Error = Error, InitError = ()>,
+{
+    fn register_container(self, container: Container) -> Self {
+        self.app_data(container)
+    }
+
+    fn container_error_handler<F>(self, handler: F) -> Self
+    where
+        F: Fn(&HttpRequest, coi::Error) -> Error + Send + Sync + 'static,
+    {
+        self.app_data(ContainerErrorConfig::new(handler))
+    }
+}
+
+/// Extensions to `actix-web`'s `Scope` struct
+///
+/// Registering a `Container` on a `Scope` lets that subtree of routes
+/// resolve dependencies from a different container than the rest of the
+/// app (e.g. a tenant-specific container under `/tenant/{id}`), since
+/// `HttpRequest::app_data` resolves the nearest registered `Container` to
+/// the matched route.
+pub trait ScopeExt {
+    /// A helper extension method to ensure the `Container` is
+    /// properly registered to work with the `inject` attribute macro.
+    fn register_container(self, container: Container) -> Self;
+}
+
+impl<T> ScopeExt for actix_web::Scope<T>
+where
+    T: ServiceFactory<ServiceRequest, Config = (), Error = Error, InitError = ()>,
+{
+    fn register_container(self, container: Container) -> Self {
+        self.app_data(container)
+    }
+}
+
+/// Extensions to `actix-web`'s `Resource` struct
+///
+/// See [`ScopeExt`] for why you'd register a
+/// `Container` below the app level.
+pub trait ResourceExt {
+    /// A helper extension method to ensure the `Container` is
+    /// properly registered to work with the `inject` attribute macro.
+    fn register_container(self, container: Container) -> Self;
+}
+
+impl<T> ResourceExt for actix_web::Resource<T>
 where
     T: ServiceFactory<ServiceRequest, Config = (), Error = Error, InitError = ()>,
 {
@@ -246,13 +309,224 @@ where
 use coi::{Container, Inject};
 pub use coi_actix_web_derive::*;
 
+/// A middleware that resolves a single [`Container::scoped`] instance per
+/// request and stores it in the request's extensions, so that every
+/// [`Injected`] extractor for that request (across middleware and handlers
+/// alike) shares the same scope rather than each extractor scoping the
+/// container independently.
+///
+/// Install it together with the root container via
+/// [`register_scoped_container`], or directly via `.wrap(...)` if you need
+/// finer control over ordering with other middleware.
+///
+/// # Warning
+///
+/// The shared-scope guarantee only holds if the `Container` passed to
+/// [`ScopedContainer::new`] is *not* also registered via
+/// [`AppExt::register_container`] (i.e. as a plain `Container` in
+/// `app_data`). [`scoped_container`] always prefers a plain `Container` in
+/// `app_data` over the scope this middleware stashes, since that's how a
+/// `Scope`/`Resource` override is recognized. Pairing `ScopedContainer`
+/// with a plain `register_container` silently defeats the "one scope per
+/// request" guarantee: every `#[inject]` extractor re-scopes the container
+/// independently instead of reusing the stashed scope. Use
+/// [`register_scoped_container`], which registers the root container as a
+/// private [`RootContainer`] instead, so the plain-`Container` branch isn't
+/// triggered by it.
+pub struct ScopedContainer {
+    container: Container,
+}
+
+impl ScopedContainer {
+    /// Creates a middleware that will scope `container` once per request.
+    pub fn new(container: Container) -> Self {
+        Self { container }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ScopedContainer
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ScopedContainerMiddleware<S>;
+    type InitError = ();
+    type Future = TransformReady<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        futures::future::ready(Ok(ScopedContainerMiddleware {
+            service,
+            container: self.container.clone(),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct ScopedContainerMiddleware<S> {
+    service: S,
+    container: Container,
+}
+
+impl<S, B> Service<ServiceRequest> for ScopedContainerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let scoped = self.container.scoped();
+        req.extensions_mut().insert(scoped);
+        let fut = self.service.call(req);
+        Box::pin(fut)
+    }
+}
+
+/// The app's root [`Container`], registered separately from any
+/// [`AppExt`]/[`ScopeExt`]/[`ResourceExt`]-registered `Container` so that
+/// [`scoped_container`] can tell the two apart: a plain `Container` found
+/// via `app_data` (whether registered on the `App`, a `Scope` or a
+/// `Resource`) is treated as an override and takes precedence over the
+/// request-wide scope stashed by [`ScopedContainerMiddleware`].
+struct RootContainer(Container);
+
+/// Registers `container` as the app's root [`Container`] and wraps the app
+/// with [`ScopedContainer`], so that every [`Injected`] extractor used
+/// within a single request shares one scope, unless a more specific
+/// `Container` has been registered on an inner `Scope` or `Resource` (see
+/// [`ScopeExt::register_container`]), in which case that one wins.
+///
+/// This is kept as a free function rather than an [`AppExt`] method because
+/// `.wrap(...)` changes `App`'s type parameter, so it can't return `Self`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use actix_web::{App, HttpServer};
+/// use coi::container;
+///
+/// # #[actix_rt::main]
+/// # async fn main() -> std::io::Result<()> {
+/// let container = container! {
+///     service => ServiceImplProvider; scoped
+/// };
+///
+/// HttpServer::new(move || {
+///     coi_actix_web::register_scoped_container(App::new(), container.clone())
+/// })
+/// .bind("127.0.0.1:8000")?
+/// .run()
+/// .await
+/// # }
+/// # use coi::{Container, Inject, Provide};
+/// # use std::sync::Arc;
+/// # struct ServiceImpl;
+/// # impl Inject for ServiceImpl {}
+/// # struct ServiceImplProvider;
+/// # impl Provide for ServiceImplProvider {
+/// #     type Output = ServiceImpl;
+/// #     fn provide(&self, _: &Container) -> coi::Result<Arc<Self::Output>> {
+/// #         Ok(Arc::new(ServiceImpl))
+/// #     }
+/// # }
+/// ```
+pub fn register_scoped_container<T>(
+    app: actix_web::App<T>,
+    container: Container,
+) -> actix_web::App<impl ServiceFactory<ServiceRequest, Config = (), Error = Error, InitError = ()>>
+where
+    T: ServiceFactory<ServiceRequest, Config = (), Error = Error, InitError = ()> + 'static,
+{
+    app.app_data(RootContainer(container.clone()))
+        .wrap(ScopedContainer::new(container))
+}
+
 use actix_web::dev::Payload;
 use actix_web::error::ErrorInternalServerError;
 use actix_web::{Error as WebError, FromRequest, HttpRequest};
-use futures::future::{err, ok, ready, Ready};
+use futures::future::{err, ready, Ready};
 use std::marker::PhantomData;
 use std::sync::Arc;
 
+/// Looks up the [`Container`] that should be used to resolve injected
+/// dependencies for `req`, in order of precedence:
+///
+/// 1. The nearest `Container` registered via [`AppExt::register_container`],
+///    [`ScopeExt::register_container`] or [`ResourceExt::register_container`]
+///    — these always override the request-wide scope below, since they
+///    exist specifically to scope a subtree of routes to a different
+///    container.
+/// 2. The per-request scope stashed by [`ScopedContainerMiddleware`], if
+///    that middleware is installed and no override from (1) applies.
+/// 3. A fresh scope taken directly from the root [`Container`] registered
+///    via [`register_scoped_container`].
+fn scoped_container(req: &HttpRequest) -> Result<Container, WebError> {
+    if let Some(container) = req.app_data::<Container>() {
+        return Ok(container.scoped());
+    }
+
+    if let Some(scoped) = req.extensions().get::<Container>() {
+        return Ok(scoped.clone());
+    }
+
+    match req.app_data::<RootContainer>() {
+        Some(root) => Ok(root.0.scoped()),
+        None => Err(ErrorInternalServerError("Container not registered")),
+    }
+}
+
+/// Lets an application customize the `actix_web::Error` returned when a
+/// `#[inject]` argument fails to resolve, instead of the default
+/// `ErrorInternalServerError`.
+///
+/// Register it with [`AppExt::container_error_handler`]. Like other
+/// extractor configuration in `actix-web` (e.g. `web::JsonConfig`), it's
+/// read out of `app_data` by the `Injected` extractors, so it can also be
+/// scoped to a `Scope` or `Resource` via their own `app_data`.
+///
+/// This config is only consulted for resolution failures (a [`coi::Error`]
+/// from a [`Container`] that was found). It has no effect on the case where
+/// no `Container` is registered for the request at all; see
+/// [`scoped_container`].
+pub struct ContainerErrorConfig {
+    handler: Box<dyn Fn(&HttpRequest, coi::Error) -> WebError + Send + Sync>,
+}
+
+impl ContainerErrorConfig {
+    /// Creates a new config from a closure mapping a failed resolution into
+    /// an `actix_web::Error`.
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: Fn(&HttpRequest, coi::Error) -> WebError + Send + Sync + 'static,
+    {
+        Self {
+            handler: Box::new(handler),
+        }
+    }
+}
+
+/// Converts a failed dependency resolution into an `actix_web::Error`,
+/// deferring to a registered [`ContainerErrorConfig`] if there is one.
+///
+/// Only called for a [`coi::Error`] out of a `Container` that [`scoped_container`]
+/// already located; it has no bearing on the "no `Container` registered"
+/// error from `scoped_container` itself, which always maps to the default
+/// `ErrorInternalServerError`.
+fn resolve_error(req: &HttpRequest, e: coi::Error) -> WebError {
+    match req.app_data::<ContainerErrorConfig>() {
+        Some(config) => (config.handler)(req, e),
+        None => ErrorInternalServerError(e),
+    }
+}
+
 #[doc(hidden)]
 pub trait ContainerKey<T>
 where
@@ -271,120 +545,76 @@ impl<T, K> Injected<T, K> {
     }
 }
 
-impl<T, K> FromRequest for Injected<Arc<T>, K>
+/// Resolves a single injected argument from a scoped [`Container`],
+/// implemented for both required (`Arc<T>`) and optional (`Option<Arc<T>>`)
+/// `#[inject]` arguments so that [`Injected`]'s `FromRequest` impl can
+/// treat them uniformly.
+#[doc(hidden)]
+pub trait Resolvable<K>: Sized {
+    fn resolve(container: &Container) -> Result<Self, coi::Error>;
+}
+
+impl<T, K> Resolvable<K> for Arc<T>
+where
+    T: Inject + ?Sized,
+    K: ContainerKey<T>,
+{
+    fn resolve(container: &Container) -> Result<Self, coi::Error> {
+        container.resolve::<T>(K::KEY)
+    }
+}
+
+impl<T, K> Resolvable<K> for Option<Arc<T>>
 where
     T: Inject + ?Sized,
     K: ContainerKey<T>,
+{
+    fn resolve(container: &Container) -> Result<Self, coi::Error> {
+        Ok(container.resolve::<T>(K::KEY).ok())
+    }
+}
+
+impl<R, K> FromRequest for Injected<R, K>
+where
+    R: Resolvable<K>,
 {
     type Error = WebError;
     type Future = Ready<Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        match req.app_data::<Container>() {
-            Some(container) => {
-                let container = container.scoped();
-                ready(
-                    container
-                        .resolve::<T>(K::KEY)
-                        .map(Injected::new)
-                        .map_err(ErrorInternalServerError),
-                )
-            }
-            None => err(ErrorInternalServerError("Container not registered")),
-        }
+        let container = match scoped_container(req) {
+            Ok(container) => container,
+            Err(e) => return err(e),
+        };
+        ready(
+            R::resolve(&container)
+                .map(Injected::new)
+                .map_err(|e| resolve_error(req, e)),
+        )
     }
 }
 
-macro_rules! injected_tuples {
-    ($(($T:ident, $K:ident)),+) => {
-        impl<$($T, $K),+> FromRequest for Injected<($(Arc<$T>),+), ($($K),+)>
-        where $(
-            $T: Inject + ?Sized + 'static,
-            $K: ContainerKey<$T>,
-        )+
-        {
-            type Error = WebError;
-            type Future = Ready<Result<Self, Self::Error>>;
-
-            fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-                match req.app_data::<Container>() {
-                    Some(container) => {
-                        let container = container.scoped();
-                        ok(Injected::new(($(
-                            {
-                                let resolved = container.resolve::<$T>(<$K as ContainerKey<$T>>::KEY)
-                                    .map_err(ErrorInternalServerError);
-                                match resolved {
-                                    Ok(r) => r,
-                                    Err(e) => return err(e),
-                                }
-                            },
-                        )+)))
-                    },
-                    None => err(ErrorInternalServerError("Container not registered"))
-                }
-            }
-        }
+/// The empty tail of an injected cons-list: see the `(R, Rest)` impl below.
+impl Resolvable<()> for () {
+    fn resolve(_: &Container) -> Result<Self, coi::Error> {
+        Ok(())
     }
 }
 
-injected_tuples!((TA, KA), (TB, KB));
-injected_tuples!((TA, KA), (TB, KB), (TC, KC));
-injected_tuples!((TA, KA), (TB, KB), (TC, KC), (TD, KD));
-injected_tuples!((TA, KA), (TB, KB), (TC, KC), (TD, KD), (TE, KE));
-injected_tuples!((TA, KA), (TB, KB), (TC, KC), (TD, KD), (TE, KE), (TF, KF));
-injected_tuples!(
-    (TA, KA),
-    (TB, KB),
-    (TC, KC),
-    (TD, KD),
-    (TE, KE),
-    (TF, KF),
-    (TG, KG)
-);
-injected_tuples!(
-    (TA, KA),
-    (TB, KB),
-    (TC, KC),
-    (TD, KD),
-    (TE, KE),
-    (TF, KF),
-    (TG, KG),
-    (TH, KH)
-);
-injected_tuples!(
-    (TA, KA),
-    (TB, KB),
-    (TC, KC),
-    (TD, KD),
-    (TE, KE),
-    (TF, KF),
-    (TG, KG),
-    (TH, KH),
-    (TI, KI)
-);
-injected_tuples!(
-    (TA, KA),
-    (TB, KB),
-    (TC, KC),
-    (TD, KD),
-    (TE, KE),
-    (TF, KF),
-    (TG, KG),
-    (TH, KH),
-    (TI, KI),
-    (TJ, KJ)
-);
-injected_tuples!(
-    (TA, KA),
-    (TB, KB),
-    (TC, KC),
-    (TD, KD),
-    (TE, KE),
-    (TF, KF),
-    (TG, KG),
-    (TH, KH),
-    (TI, KI),
-    (TJ, KJ),
-    (TK, KK)
-);
+/// Resolves a handler's `#[inject]` arguments one at a time from a
+/// recursive head/tail structure rather than a fixed-arity tuple, so that a
+/// handler can take any number of injected arguments. `R`/`K` are the head
+/// argument's resolvable type and container key; `Rest`/`KRest` are the
+/// same pair of lists for the remaining arguments, bottoming out at `()`.
+/// Because this is all folded into `Resolvable`, `Injected`'s single
+/// `FromRequest` impl above covers any arity without change, and the whole
+/// list still resolves from one scoped [`Container`].
+impl<R, Rest, K, KRest> Resolvable<(K, KRest)> for (R, Rest)
+where
+    R: Resolvable<K>,
+    Rest: Resolvable<KRest>,
+{
+    fn resolve(container: &Container) -> Result<Self, coi::Error> {
+        Ok((R::resolve(container)?, Rest::resolve(container)?))
+    }
+}